@@ -1,31 +1,27 @@
 use std::collections::BTreeMap;
 
-use anyhow::{bail, Result};
-use serde::{Serialize, Serializer};
+use serde::Serialize;
 
-use crate::transaction::{Transaction, TransactionType};
+use crate::error::LedgerError;
+use crate::transaction::{Transaction, TransactionType, TxState};
 use crate::types::{Amount, ClientId, TransactionId};
 
+type Result<T> = std::result::Result<T, LedgerError>;
+
 #[derive(Debug, Serialize)]
 pub struct Account {
     #[serde(rename = "client")]
     pub client_id: ClientId,
     #[serde(skip_serializing)]
     pub transactions: BTreeMap<TransactionId, Transaction>,
-    #[serde(serialize_with = "serialize_amount")]
     pub available: Amount,
-    #[serde(serialize_with = "serialize_amount")]
     pub held: Amount,
-    #[serde(serialize_with = "serialize_amount")]
     pub total: Amount,
     pub locked: bool,
-}
-
-fn serialize_amount<S>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(&format!("{:.4}", amount))
+    /// Whether disputes against withdrawals are accepted. Off by default, so
+    /// `handle_dispute` rejects anything but deposits unless opted in.
+    #[serde(skip_serializing)]
+    pub allow_withdrawal_disputes: bool,
 }
 
 impl Account {
@@ -33,146 +29,209 @@ impl Account {
         Self {
             client_id,
             transactions: BTreeMap::new(),
-            available: 0.0,
-            held: 0.0,
-            total: 0.0,
+            available: Amount::ZERO,
+            held: Amount::ZERO,
+            total: Amount::ZERO,
             locked: false,
+            allow_withdrawal_disputes: false,
         }
     }
 
+    pub fn with_withdrawal_disputes_allowed(mut self, allowed: bool) -> Self {
+        self.allow_withdrawal_disputes = allowed;
+        self
+    }
+
     fn handle_deposit(&mut self, transaction: Transaction) -> Result<()> {
-        let amount = transaction.amount.expect("malformed transaction");
-        self.available += amount;
-        self.total += amount;
+        let amount = transaction.amount.ok_or(LedgerError::MissingAmount)?;
+
+        let available = self
+            .available
+            .checked_add(amount)
+            .ok_or(LedgerError::AmountOverflow)?;
+        let total = self
+            .total
+            .checked_add(amount)
+            .ok_or(LedgerError::AmountOverflow)?;
+        self.available = available;
+        self.total = total;
         self.transactions
             .insert(transaction.transaction_id, transaction);
         Ok(())
     }
 
     fn handle_withdraw(&mut self, transaction: Transaction) -> Result<()> {
-        let amount = transaction.amount.expect("malformed transaction");
+        let amount = transaction.amount.ok_or(LedgerError::MissingAmount)?;
 
         if self.available < amount {
-            bail!("withdraw error: insufficient funds");
+            return Err(LedgerError::InsufficientFunds);
         }
 
-        self.available -= amount;
-        self.total -= amount;
+        let available = self
+            .available
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow)?;
+        let total = self
+            .total
+            .checked_sub(amount)
+            .ok_or(LedgerError::AmountOverflow)?;
+        self.available = available;
+        self.total = total;
         self.transactions
             .insert(transaction.transaction_id, transaction);
         Ok(())
     }
 
     fn handle_dispute(&mut self, transaction: Transaction) -> Result<()> {
-        match self.transactions.get_mut(&transaction.transaction_id) {
-            Some(referenced_transaction) => {
-                if referenced_transaction.under_dispute {
-                    bail!(
-                        "dispute error: transaction {} is already under dispute",
-                        referenced_transaction.transaction_id
-                    );
-                }
-
-                match referenced_transaction.transaction_type {
-                    TransactionType::Deposit => {
-                        let amount = referenced_transaction
-                            .amount
-                            .expect("malformed transaction");
-
-                        if self.available < amount {
-                            bail!("dispute error: insufficient funds");
-                        }
-
-                        self.available -= amount;
-                        self.held += amount;
-                    }
-                    TransactionType::Withdrawal => {
-                        bail!("dispute error: withdrawal is not a valid target");
-                    }
-                    _ => panic!("the 'impossible' happened"),
-                }
-
-                referenced_transaction.under_dispute = true;
+        let referenced_transaction = self
+            .transactions
+            .get_mut(&transaction.transaction_id)
+            .ok_or(LedgerError::UnknownTransaction {
+                client: transaction.client_id,
+                tx: transaction.transaction_id,
+            })?;
+
+        match referenced_transaction.state {
+            TxState::Processed => {}
+            TxState::Disputed => return Err(LedgerError::AlreadyDisputed),
+            TxState::Resolved | TxState::ChargedBack => {
+                return Err(LedgerError::TransactionClosed)
             }
-            None => {
-                bail!(format!(
-                    "dispute error: transaction {} not found for client {}",
-                    transaction.transaction_id, transaction.client_id
-                ));
+        }
+
+        let disputing_withdrawal = match referenced_transaction.transaction_type {
+            TransactionType::Deposit => false,
+            TransactionType::Withdrawal if self.allow_withdrawal_disputes => true,
+            _ => return Err(LedgerError::DisputeTargetNotDeposit),
+        };
+
+        let amount = referenced_transaction
+            .amount
+            .ok_or(LedgerError::MissingAmount)?;
+
+        if disputing_withdrawal {
+            // The funds already left `available` when the withdrawal was
+            // processed, so holding them back only needs `held`/`total` to
+            // grow to represent the pending reversal.
+            let held = self.held.checked_add(amount).ok_or(LedgerError::AmountOverflow)?;
+            let total = self.total.checked_add(amount).ok_or(LedgerError::AmountOverflow)?;
+            self.held = held;
+            self.total = total;
+        } else {
+            if self.available < amount {
+                return Err(LedgerError::InsufficientFunds);
             }
+
+            let available = self
+                .available
+                .checked_sub(amount)
+                .ok_or(LedgerError::AmountOverflow)?;
+            let held = self.held.checked_add(amount).ok_or(LedgerError::AmountOverflow)?;
+            self.available = available;
+            self.held = held;
         }
 
+        referenced_transaction.state = TxState::Disputed;
+
         Ok(())
     }
 
     fn handle_resolve(&mut self, transaction: Transaction) -> Result<()> {
-        match self.transactions.get_mut(&transaction.transaction_id) {
-            Some(referenced_transaction) => {
-                if !referenced_transaction.under_dispute {
-                    bail!(
-                        "resolve error: transaction {} is not under dispute",
-                        referenced_transaction.transaction_id
-                    );
-                }
-
-                match referenced_transaction.transaction_type {
-                    TransactionType::Deposit => {
-                        let amount = referenced_transaction
-                            .amount
-                            .expect("malformed transaction");
-                        self.available += amount;
-                        self.held -= amount;
-                    }
-                    TransactionType::Withdrawal => {
-                        bail!("resolve error: withdrawal is not a valid target");
-                    }
-                    _ => panic!("the 'impossible' happened"),
-                }
-
-                referenced_transaction.under_dispute = false;
-            }
-            None => {
-                bail!(format!(
-                    "resolve error: transaction {} not found for client {}",
-                    transaction.transaction_id, transaction.client_id
-                ));
-            }
+        let referenced_transaction = self
+            .transactions
+            .get_mut(&transaction.transaction_id)
+            .ok_or(LedgerError::UnknownTransaction {
+                client: transaction.client_id,
+                tx: transaction.transaction_id,
+            })?;
+
+        if referenced_transaction.state != TxState::Disputed {
+            return Err(LedgerError::NotDisputed);
         }
 
+        let amount = referenced_transaction
+            .amount
+            .ok_or(LedgerError::MissingAmount)?;
+
+        // `handle_dispute` only ever disputes deposits or (when enabled)
+        // withdrawals, so the referenced transaction can only be one of those two.
+        if matches!(referenced_transaction.transaction_type, TransactionType::Withdrawal) {
+            // Releases the hold without touching `available`, returning the
+            // account to the state it was in right after the withdrawal
+            // went through.
+            let held = self.held.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            let total = self.total.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            self.held = held;
+            self.total = total;
+        } else {
+            let available = self
+                .available
+                .checked_add(amount)
+                .ok_or(LedgerError::AmountOverflow)?;
+            let held = self.held.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            self.available = available;
+            self.held = held;
+        }
+
+        referenced_transaction.state = TxState::Resolved;
+
         Ok(())
     }
 
     fn handle_chargeback(&mut self, transaction: Transaction) -> Result<()> {
-        match self.transactions.get_mut(&transaction.transaction_id) {
-            Some(referenced_transaction) => {
-                if !referenced_transaction.under_dispute {
-                    bail!(
-                        "chargeback error: transaction {} is not under dispute",
-                        referenced_transaction.transaction_id
-                    );
-                }
-
-                let amount = referenced_transaction
-                    .amount
-                    .expect("malformed transaction");
-                self.held -= amount;
-                self.total -= amount;
-                self.locked = true;
-            }
-            None => {
-                bail!(format!(
-                    "chargeback error: transaction {} not found for client {}",
-                    transaction.transaction_id, transaction.client_id
-                ));
-            }
+        let referenced_transaction = self
+            .transactions
+            .get_mut(&transaction.transaction_id)
+            .ok_or(LedgerError::UnknownTransaction {
+                client: transaction.client_id,
+                tx: transaction.transaction_id,
+            })?;
+
+        if referenced_transaction.state != TxState::Disputed {
+            return Err(LedgerError::NotDisputed);
+        }
+
+        let amount = referenced_transaction
+            .amount
+            .ok_or(LedgerError::MissingAmount)?;
+
+        // `handle_dispute` only ever disputes deposits or (when enabled)
+        // withdrawals, so the referenced transaction can only be one of those two.
+        let (available, held, total) = if matches!(
+            referenced_transaction.transaction_type,
+            TransactionType::Withdrawal
+        ) {
+            // Reverses the withdrawal: the funds come back into `available`
+            // and the hold is released; `total` is unaffected since it
+            // already accounted for the held funds.
+            let available = self
+                .available
+                .checked_add(amount)
+                .ok_or(LedgerError::AmountOverflow)?;
+            let held = self.held.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            (available, held, self.total)
+        } else {
+            let held = self.held.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            let total = self.total.checked_sub(amount).ok_or(LedgerError::AmountOverflow)?;
+            (self.available, held, total)
+        };
+
+        if total < Amount::ZERO {
+            return Err(LedgerError::NegativeBalance);
         }
 
+        self.available = available;
+        self.held = held;
+        self.total = total;
+        self.locked = true;
+        referenced_transaction.state = TxState::ChargedBack;
+
         Ok(())
     }
 
     pub fn execute(&mut self, transaction: Transaction) -> Result<()> {
         if self.locked {
-            bail!("account locked");
+            return Err(LedgerError::FrozenAccount);
         }
 
         match transaction.transaction_type {
@@ -188,10 +247,12 @@ impl Account {
     }
 
     fn check_consistency(&self) {
-        let eps = 0.0001;
-        assert!((self.total - (self.available + self.held)).abs() < eps);
-        assert!((self.held - (self.total - self.available)).abs() < eps);
-        assert!((self.available - (self.total - self.held)).abs() < eps);
+        assert_eq!(
+            self.total,
+            self.available
+                .checked_add(self.held)
+                .expect("balance overflow")
+        );
     }
 }
 
@@ -201,23 +262,27 @@ mod tests {
 
     use super::*;
 
-    fn deposit(client_id: ClientId, amount: Amount) -> Transaction {
+    fn amount(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    fn deposit(client_id: ClientId, amount: &str) -> Transaction {
         Transaction {
             client_id,
             transaction_type: TransactionType::Deposit,
             transaction_id: next_transaction_id(),
-            amount: Some(amount),
-            under_dispute: false,
+            amount: Some(self::amount(amount)),
+            state: TxState::Processed,
         }
     }
 
-    fn withdrawal(client_id: ClientId, amount: Amount) -> Transaction {
+    fn withdrawal(client_id: ClientId, amount: &str) -> Transaction {
         Transaction {
             client_id,
             transaction_type: TransactionType::Withdrawal,
             transaction_id: next_transaction_id(),
-            amount: Some(amount),
-            under_dispute: false,
+            amount: Some(self::amount(amount)),
+            state: TxState::Processed,
         }
     }
 
@@ -227,7 +292,7 @@ mod tests {
             transaction_type: TransactionType::Dispute,
             transaction_id,
             amount: None,
-            under_dispute: false,
+            state: TxState::Processed,
         }
     }
 
@@ -237,7 +302,7 @@ mod tests {
             transaction_type: TransactionType::Resolve,
             transaction_id,
             amount: None,
-            under_dispute: false,
+            state: TxState::Processed,
         }
     }
 
@@ -247,14 +312,14 @@ mod tests {
             transaction_type: TransactionType::Chargeback,
             transaction_id,
             amount: None,
-            under_dispute: false,
+            state: TxState::Processed,
         }
     }
 
-    fn check(account: &Account, available: Amount, held: Amount, total: Amount, locked: bool) {
-        assert_eq!(account.available, available);
-        assert_eq!(account.held, held);
-        assert_eq!(account.total, total);
+    fn check(account: &Account, available: &str, held: &str, total: &str, locked: bool) {
+        assert_eq!(account.available, amount(available));
+        assert_eq!(account.held, amount(held));
+        assert_eq!(account.total, amount(total));
         assert_eq!(account.locked, locked);
     }
 
@@ -267,31 +332,48 @@ mod tests {
     fn deposit_withdraw_flow() {
         let client_id = 0;
         let mut account = Account::new(client_id);
-        assert!(account.execute(deposit(client_id, 2.0)).is_ok());
-        check(&account, 2.0, 0.0, 2.0, false);
-        assert!(account.execute(deposit(client_id, 3.0)).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
-        assert!(account.execute(withdrawal(client_id, 1.0)).is_ok());
-        check(&account, 4.0, 0.0, 4.0, false);
+        assert!(account.execute(deposit(client_id, "2.0")).is_ok());
+        check(&account, "2.0", "0.0", "2.0", false);
+        assert!(account.execute(deposit(client_id, "3.0")).is_ok());
+        check(&account, "5.0", "0.0", "5.0", false);
+        assert!(account.execute(withdrawal(client_id, "1.0")).is_ok());
+        check(&account, "4.0", "0.0", "4.0", false);
     }
 
     #[test]
     fn withdrawal_should_not_result_in_negative_balance() {
         let client_id = 0;
         let mut account = Account::new(client_id);
-        assert!(account.execute(deposit(client_id, 2.0)).is_ok());
-        check(&account, 2.0, 0.0, 2.0, false);
-        assert!(account.execute(withdrawal(client_id, 3.0)).is_err());
-        check(&account, 2.0, 0.0, 2.0, false);
+        assert!(account.execute(deposit(client_id, "2.0")).is_ok());
+        check(&account, "2.0", "0.0", "2.0", false);
+        assert!(account.execute(withdrawal(client_id, "3.0")).is_err());
+        check(&account, "2.0", "0.0", "2.0", false);
+    }
+
+    #[test]
+    fn deposit_without_amount_is_rejected_rather_than_panicking() {
+        let mut account = Account::new(0);
+        let deposit_1 = Transaction {
+            client_id: account.client_id,
+            transaction_type: TransactionType::Deposit,
+            transaction_id: next_transaction_id(),
+            amount: None,
+            state: TxState::Processed,
+        };
+        assert_eq!(
+            account.execute(deposit_1).unwrap_err(),
+            LedgerError::MissingAmount
+        );
+        check(&account, "0.0", "0.0", "0.0", false);
     }
 
     #[test]
     fn dispute_of_disputed_transaction_should_fail() {
         let client_id = 0;
         let mut account = Account::new(client_id);
-        let deposit_1 = deposit(client_id, 5.0);
+        let deposit_1 = deposit(client_id, "5.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         assert!(account
             .execute(dispute(client_id, deposit_1.transaction_id))
             .is_ok());
@@ -303,11 +385,11 @@ mod tests {
     #[test]
     fn dispute_should_not_result_in_negative_balance() {
         let mut account = Account::new(0);
-        let deposit_1 = deposit(account.client_id, 5.0);
+        let deposit_1 = deposit(account.client_id, "5.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
-        assert!(account.execute(withdrawal(account.client_id, 3.0)).is_ok());
-        check(&account, 2.0, 0.0, 2.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
+        assert!(account.execute(withdrawal(account.client_id, "3.0")).is_ok());
+        check(&account, "2.0", "0.0", "2.0", false);
         assert!(account
             .execute(dispute(account.client_id, deposit_1.transaction_id))
             .is_err());
@@ -316,52 +398,108 @@ mod tests {
     #[test]
     fn dispute_resolve_flow() {
         let mut account = Account::new(0);
-        let deposit_1 = deposit(account.client_id, 5.0);
+        let deposit_1 = deposit(account.client_id, "5.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         assert!(account
             .execute(dispute(account.client_id, deposit_1.transaction_id))
             .is_ok());
-        check(&account, 0.0, 5.0, 5.0, false);
-        assert!(
+        check(&account, "0.0", "5.0", "5.0", false);
+        assert_eq!(
             account
                 .transactions
                 .get(&deposit_1.transaction_id)
                 .unwrap()
-                .under_dispute
+                .state,
+            TxState::Disputed
         );
         assert!(account
             .execute(resolve(account.client_id, deposit_1.transaction_id))
             .is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
-        assert!(
-            !account
+        check(&account, "5.0", "0.0", "5.0", false);
+        assert_eq!(
+            account
                 .transactions
                 .get(&deposit_1.transaction_id)
                 .unwrap()
-                .under_dispute
+                .state,
+            TxState::Resolved
+        );
+    }
+
+    #[test]
+    fn resolved_transaction_cannot_be_disputed_again() {
+        let mut account = Account::new(0);
+        let deposit_1 = deposit(account.client_id, "5.0");
+        assert!(account.execute(deposit_1.clone()).is_ok());
+        assert!(account
+            .execute(dispute(account.client_id, deposit_1.transaction_id))
+            .is_ok());
+        assert!(account
+            .execute(resolve(account.client_id, deposit_1.transaction_id))
+            .is_ok());
+        assert_eq!(
+            account
+                .execute(dispute(account.client_id, deposit_1.transaction_id))
+                .unwrap_err(),
+            LedgerError::TransactionClosed
         );
     }
 
     #[test]
     fn dispute_of_withdrawal_should_fail() {
         let mut account = Account::new(0);
-        assert!(account.execute(deposit(account.client_id, 5.0)).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
-        let withdrawal_1 = withdrawal(account.client_id, 3.0);
+        assert!(account.execute(deposit(account.client_id, "5.0")).is_ok());
+        check(&account, "5.0", "0.0", "5.0", false);
+        let withdrawal_1 = withdrawal(account.client_id, "3.0");
         assert!(account.execute(withdrawal_1.clone()).is_ok());
-        check(&account, 2.0, 0.0, 2.0, false);
+        check(&account, "2.0", "0.0", "2.0", false);
         assert!(account
             .execute(dispute(account.client_id, withdrawal_1.transaction_id))
             .is_err());
     }
 
+    #[test]
+    fn dispute_of_withdrawal_allowed_when_enabled() {
+        let mut account = Account::new(0).with_withdrawal_disputes_allowed(true);
+        assert!(account.execute(deposit(account.client_id, "5.0")).is_ok());
+        let withdrawal_1 = withdrawal(account.client_id, "3.0");
+        assert!(account.execute(withdrawal_1.clone()).is_ok());
+        check(&account, "2.0", "0.0", "2.0", false);
+        assert!(account
+            .execute(dispute(account.client_id, withdrawal_1.transaction_id))
+            .is_ok());
+        // The withdrawn funds are held again, but the withdrawal is not undone.
+        check(&account, "2.0", "3.0", "5.0", false);
+        assert!(account
+            .execute(resolve(account.client_id, withdrawal_1.transaction_id))
+            .is_ok());
+        check(&account, "2.0", "0.0", "2.0", false);
+    }
+
+    #[test]
+    fn chargeback_of_disputed_withdrawal_reverses_it() {
+        let mut account = Account::new(0).with_withdrawal_disputes_allowed(true);
+        assert!(account.execute(deposit(account.client_id, "5.0")).is_ok());
+        let withdrawal_1 = withdrawal(account.client_id, "3.0");
+        assert!(account.execute(withdrawal_1.clone()).is_ok());
+        assert!(account
+            .execute(dispute(account.client_id, withdrawal_1.transaction_id))
+            .is_ok());
+        check(&account, "2.0", "3.0", "5.0", false);
+        assert!(account
+            .execute(chargeback(account.client_id, withdrawal_1.transaction_id))
+            .is_ok());
+        // The withdrawn funds are returned to the client, and the account is locked.
+        check(&account, "5.0", "0.0", "5.0", true);
+    }
+
     #[test]
     fn resolve_of_undisputed_transaction_should_fail() {
         let mut account = Account::new(0);
-        let deposit_1 = deposit(account.client_id, 5.0);
+        let deposit_1 = deposit(account.client_id, "5.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         assert!(account
             .execute(resolve(account.client_id, deposit_1.transaction_id))
             .is_err());
@@ -370,9 +508,9 @@ mod tests {
     #[test]
     fn chargeback_of_undisputed_transaction_should_fail() {
         let mut account = Account::new(0);
-        let deposit_1 = deposit(account.client_id, 5.0);
+        let deposit_1 = deposit(account.client_id, "5.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         assert!(account
             .execute(chargeback(account.client_id, deposit_1.transaction_id))
             .is_err());
@@ -381,30 +519,30 @@ mod tests {
     #[test]
     fn chargeback_flow() {
         let mut account = Account::new(0);
-        let deposit_1 = deposit(account.client_id, 2.0);
+        let deposit_1 = deposit(account.client_id, "2.0");
         assert!(account.execute(deposit_1.clone()).is_ok());
-        let deposit_2 = deposit(account.client_id, 3.0);
+        let deposit_2 = deposit(account.client_id, "3.0");
         assert!(account.execute(deposit_2.clone()).is_ok());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         // Since the transaction is not under dispute, chargeback should fail
         assert!(account
             .execute(chargeback(account.client_id, deposit_1.transaction_id))
             .is_err());
-        check(&account, 5.0, 0.0, 5.0, false);
+        check(&account, "5.0", "0.0", "5.0", false);
         // Dispute
         assert!(account
             .execute(dispute(account.client_id, deposit_1.transaction_id))
             .is_ok());
         // The transaction is now under dispute, the account is not locked
-        check(&account, 3.0, 2.0, 5.0, false);
+        check(&account, "3.0", "2.0", "5.0", false);
         assert!(account
             .execute(chargeback(account.client_id, deposit_1.transaction_id))
             .is_ok());
         // The account is now locked
-        check(&account, 3.0, 0.0, 3.0, true);
+        check(&account, "3.0", "0.0", "3.0", true);
         // All subsequent transactions should fail
-        assert!(account.execute(deposit(account.client_id, 7.0)).is_err());
-        assert!(account.execute(withdrawal(account.client_id, 3.0)).is_err());
+        assert!(account.execute(deposit(account.client_id, "7.0")).is_err());
+        assert!(account.execute(withdrawal(account.client_id, "3.0")).is_err());
         assert!(account
             .execute(dispute(account.client_id, deposit_2.transaction_id))
             .is_err());
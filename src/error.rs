@@ -0,0 +1,68 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::types::{ClientId, TransactionId};
+
+/// Reasons a transaction can be rejected by [`crate::account::Account::execute`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LedgerError {
+    FrozenAccount,
+    MissingAmount,
+    AmountOverflow,
+    InsufficientFunds,
+    UnknownTransaction { client: ClientId, tx: TransactionId },
+    AlreadyDisputed,
+    TransactionClosed,
+    NotDisputed,
+    DisputeTargetNotDeposit,
+    NegativeBalance,
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::FrozenAccount => write!(f, "account is locked"),
+            LedgerError::MissingAmount => {
+                write!(f, "transaction requires an amount but none was provided")
+            }
+            LedgerError::AmountOverflow => write!(f, "amount overflow"),
+            LedgerError::InsufficientFunds => write!(f, "insufficient funds"),
+            LedgerError::UnknownTransaction { client, tx } => {
+                write!(f, "transaction {tx} not found for client {client}")
+            }
+            LedgerError::AlreadyDisputed => write!(f, "transaction is already under dispute"),
+            LedgerError::TransactionClosed => {
+                write!(f, "transaction was already resolved or charged back")
+            }
+            LedgerError::NotDisputed => write!(f, "transaction is not under dispute"),
+            LedgerError::DisputeTargetNotDeposit => write!(f, "only deposits can be disputed"),
+            LedgerError::NegativeBalance => write!(f, "operation would leave a negative balance"),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// Serializes as the variant's snake_case name, so a rejection log stays one
+/// flat CSV/JSON row regardless of which variant carries extra fields.
+impl Serialize for LedgerError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name = match self {
+            LedgerError::FrozenAccount => "frozen_account",
+            LedgerError::MissingAmount => "missing_amount",
+            LedgerError::AmountOverflow => "amount_overflow",
+            LedgerError::InsufficientFunds => "insufficient_funds",
+            LedgerError::UnknownTransaction { .. } => "unknown_transaction",
+            LedgerError::AlreadyDisputed => "already_disputed",
+            LedgerError::TransactionClosed => "transaction_closed",
+            LedgerError::NotDisputed => "not_disputed",
+            LedgerError::DisputeTargetNotDeposit => "dispute_target_not_deposit",
+            LedgerError::NegativeBalance => "negative_balance",
+        };
+        serializer.serialize_str(name)
+    }
+}
@@ -1,23 +1,73 @@
 mod account;
+mod error;
+mod server;
 mod transaction;
 mod types;
 
-use std::{collections::BTreeMap, io};
+use std::{collections::BTreeMap, io, sync::mpsc, thread};
 
 use anyhow::Result;
 use clap::Parser;
+use serde::Serialize;
 
 use account::Account;
+use error::LedgerError;
 use transaction::{Transaction, TransactionType};
-use types::ClientId;
+use types::{Amount, ClientId, TransactionId};
 
-fn process_csv<Input, Output>(
+/// A transaction `process_csv`/`process_csv_parallel` could not apply, paired
+/// with the reason why.
+#[derive(Debug, Serialize)]
+struct RejectedTransaction {
+    #[serde(rename = "type")]
+    transaction_type: TransactionType,
+    #[serde(rename = "client")]
+    client_id: ClientId,
+    #[serde(rename = "tx")]
+    transaction_id: TransactionId,
+    amount: Option<Amount>,
+    error: LedgerError,
+}
+
+/// Applies `transaction` to its account in `accounts`, returning the
+/// rejection record if it was refused.
+fn execute(
+    accounts: &mut BTreeMap<ClientId, Account>,
+    transaction: Transaction,
+    allow_withdrawal_disputes: bool,
+) -> Option<RejectedTransaction> {
+    let account = accounts.entry(transaction.client_id).or_insert_with(|| {
+        Account::new(transaction.client_id)
+            .with_withdrawal_disputes_allowed(allow_withdrawal_disputes)
+    });
+
+    let transaction_type = transaction.transaction_type;
+    let client_id = transaction.client_id;
+    let transaction_id = transaction.transaction_id;
+    let amount = transaction.amount;
+
+    account
+        .execute(transaction)
+        .err()
+        .map(|error| RejectedTransaction {
+            transaction_type,
+            client_id,
+            transaction_id,
+            amount,
+            error,
+        })
+}
+
+fn process_csv<Input, Output, Reject>(
     reader: &mut csv::Reader<Input>,
     writer: &mut csv::Writer<Output>,
+    rejections: &mut csv::Writer<Reject>,
+    allow_withdrawal_disputes: bool,
 ) -> Result<()>
 where
     Input: io::Read,
     Output: io::Write,
+    Reject: io::Write,
 {
     let mut accounts: BTreeMap<ClientId, Account> = BTreeMap::new();
 
@@ -30,29 +80,114 @@ where
         let transaction: Transaction = result?;
 
         if transaction.requires_amount() && transaction.amount.is_none() {
-            eprintln!(
-                "Warning: transaction {} requires an amount but none was provided",
-                transaction.transaction_id
-            );
+            rejections.serialize(RejectedTransaction {
+                transaction_type: transaction.transaction_type,
+                client_id: transaction.client_id,
+                transaction_id: transaction.transaction_id,
+                amount: transaction.amount,
+                error: LedgerError::MissingAmount,
+            })?;
             continue;
         }
 
-        let account = accounts
-            .entry(transaction.client_id)
-            .or_insert_with(|| Account::new(transaction.client_id));
-        let transaction_id = transaction.transaction_id;
-        let transaction_type = transaction.transaction_type;
-
-        if let Err(e) = account.execute(transaction) {
-            if matches!(
-                transaction_type,
-                TransactionType::Deposit | TransactionType::Withdrawal
-            ) {
-                eprintln!("Warning: transaction {} failed: {}", transaction_id, e)
-            } else {
-                eprintln!("Warning: transaction failed: {}", e)
+        if let Some(rejected) = execute(&mut accounts, transaction, allow_withdrawal_disputes) {
+            rejections.serialize(rejected)?;
+        }
+    }
+
+    for (_account_id, account) in accounts.into_iter() {
+        writer.serialize(account)?;
+    }
+
+    writer.flush()?;
+    rejections.flush()?;
+    Ok(())
+}
+
+/// Like `process_csv`, but shards accounts across `thread_count` worker
+/// threads by `client_id % thread_count`. Each client's transactions are
+/// routed to the same worker through a bounded channel, so per-client
+/// ordering (required for correct dispute/resolve/chargeback handling) is
+/// preserved even though clients are processed concurrently.
+///
+/// Rejections are tagged with the input row's sequence number and sorted
+/// back into that order before being written, so the rejection log reads
+/// the same as `process_csv`'s regardless of which worker a client landed
+/// on or how fast each worker drained its channel.
+fn process_csv_parallel<Input, Output, Reject>(
+    reader: &mut csv::Reader<Input>,
+    writer: &mut csv::Writer<Output>,
+    rejections: &mut csv::Writer<Reject>,
+    thread_count: usize,
+    allow_withdrawal_disputes: bool,
+) -> Result<()>
+where
+    Input: io::Read,
+    Output: io::Write,
+    Reject: io::Write,
+{
+    let mut senders = Vec::with_capacity(thread_count);
+    let mut handles = Vec::with_capacity(thread_count);
+
+    for _ in 0..thread_count {
+        let (sender, receiver) = mpsc::sync_channel::<(u64, Transaction)>(1024);
+        senders.push(sender);
+        handles.push(thread::spawn(move || {
+            let mut accounts: BTreeMap<ClientId, Account> = BTreeMap::new();
+            let mut rejected = Vec::new();
+            for (sequence, transaction) in receiver {
+                if let Some(r) = execute(&mut accounts, transaction, allow_withdrawal_disputes) {
+                    rejected.push((sequence, r));
+                }
             }
+            (accounts, rejected)
+        }));
+    }
+
+    let mut rejected = Vec::new();
+
+    for (sequence, result) in reader.deserialize().enumerate() {
+        let sequence = sequence as u64;
+
+        if let Err(e) = result {
+            eprintln!("Warning: {}", e);
+            continue;
         }
+
+        let transaction: Transaction = result?;
+
+        if transaction.requires_amount() && transaction.amount.is_none() {
+            rejected.push((
+                sequence,
+                RejectedTransaction {
+                    transaction_type: transaction.transaction_type,
+                    client_id: transaction.client_id,
+                    transaction_id: transaction.transaction_id,
+                    amount: transaction.amount,
+                    error: LedgerError::MissingAmount,
+                },
+            ));
+            continue;
+        }
+
+        let worker = transaction.client_id as usize % thread_count;
+        senders[worker]
+            .send((sequence, transaction))
+            .expect("worker thread hung up unexpectedly");
+    }
+
+    drop(senders);
+
+    let mut accounts: BTreeMap<ClientId, Account> = BTreeMap::new();
+    for handle in handles {
+        let (worker_accounts, worker_rejected) = handle.join().expect("worker thread panicked");
+        accounts.extend(worker_accounts);
+        rejected.extend(worker_rejected);
+    }
+
+    rejected.sort_by_key(|(sequence, _)| *sequence);
+    for (_sequence, rejected) in rejected {
+        rejections.serialize(rejected)?;
     }
 
     for (_account_id, account) in accounts.into_iter() {
@@ -60,41 +195,122 @@ where
     }
 
     writer.flush()?;
+    rejections.flush()?;
     Ok(())
 }
 
-fn process_csv_file(filename: &str) -> Result<()> {
+fn process_csv_file(
+    filename: &str,
+    rejected_filename: Option<&str>,
+    threads: usize,
+    allow_withdrawal_disputes: bool,
+) -> Result<()> {
     let mut reader = csv::ReaderBuilder::new()
         .flexible(true)
         .has_headers(true)
         .trim(csv::Trim::All)
         .from_path(filename)?;
     let mut writer = csv::Writer::from_writer(io::stdout());
-    process_csv(&mut reader, &mut writer)
+    let threads = threads.max(1);
+
+    match rejected_filename {
+        Some(path) => {
+            let mut rejections = csv::Writer::from_path(path)?;
+            if threads == 1 {
+                process_csv(&mut reader, &mut writer, &mut rejections, allow_withdrawal_disputes)
+            } else {
+                process_csv_parallel(
+                    &mut reader,
+                    &mut writer,
+                    &mut rejections,
+                    threads,
+                    allow_withdrawal_disputes,
+                )
+            }
+        }
+        None => {
+            let mut rejections = csv::Writer::from_writer(io::sink());
+            if threads == 1 {
+                process_csv(&mut reader, &mut writer, &mut rejections, allow_withdrawal_disputes)
+            } else {
+                process_csv_parallel(
+                    &mut reader,
+                    &mut writer,
+                    &mut rejections,
+                    threads,
+                    allow_withdrawal_disputes,
+                )
+            }
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
-struct Args {
+#[command(subcommand_required = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Process a CSV file of transactions and print final balances to stdout.
+    Process(ProcessArgs),
+    /// Run a server that ingests transactions over TCP and answers balance queries.
+    Serve(ServeArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ProcessArgs {
     filename: String,
+    /// Optional path to write rejected transactions and their rejection reason to, as CSV.
+    #[arg(long)]
+    rejected: Option<String>,
+    /// Number of worker threads to shard processing across by client id.
+    /// Defaults to single-threaded.
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    /// Allow disputes against withdrawals, not just deposits.
+    #[arg(long)]
+    allow_withdrawal_disputes: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct ServeArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    addr: String,
+    /// Allow disputes against withdrawals, not just deposits.
+    #[arg(long)]
+    allow_withdrawal_disputes: bool,
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
-    process_csv_file(&args.filename)
+    match Cli::parse().command {
+        Command::Process(args) => process_csv_file(
+            &args.filename,
+            args.rejected.as_deref(),
+            args.threads,
+            args.allow_withdrawal_disputes,
+        ),
+        Command::Serve(args) => server::run(&args.addr, args.allow_withdrawal_disputes),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    fn run_process_csv(input: &str) -> anyhow::Result<String> {
+    fn run_process_csv(input: &str) -> anyhow::Result<(String, String)> {
         let mut reader = csv::ReaderBuilder::new()
             .flexible(true)
             .has_headers(true)
             .trim(csv::Trim::All)
             .from_reader(input.as_bytes());
         let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
-        super::process_csv(&mut reader, &mut writer)?;
+        let mut rejections = csv::WriterBuilder::new().from_writer(vec![]);
+        super::process_csv(&mut reader, &mut writer, &mut rejections, false)?;
         let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
-        Ok(output)
+        let rejected = String::from_utf8(rejections.into_inner().unwrap()).unwrap();
+        Ok((output, rejected))
     }
 
     #[test]
@@ -125,10 +341,95 @@ chargeback,4,8
 client,available,held,total,locked
 1,1.5000,0.0000,1.5000,false
 2,2.0000,0.0000,2.0000,false
-3,5.0000,1.0170,6.0170,false
+3,6.0170,0.0000,6.0170,false
 4,4.0000,0.0000,4.0000,true
 ";
-        let output = run_process_csv(input).unwrap();
+        let expected_rejected = "\
+type,client,tx,amount,error
+withdrawal,2,5,3.0000,insufficient_funds
+chargeback,3,7,,not_disputed
+dispute,3,7,,transaction_closed
+";
+        let (output, rejected) = run_process_csv(input).unwrap();
         assert_eq!(output, expected_output);
+        assert_eq!(rejected, expected_rejected);
+    }
+
+    #[test]
+    fn process_csv_parallel_matches_single_threaded() {
+        let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,2,2,2.0
+deposit,1,3,2.0
+withdrawal,1,4,1.5
+withdrawal,2,5,3.0
+deposit,3,6,5.0
+deposit,3,7,1.017
+dispute,3,7
+resolve,3,7
+chargeback,3,7
+dispute,3,7
+deposit,4,8,3.0
+deposit,4,9,4.0
+dispute,4,8
+chargeback,4,8
+";
+        let expected_output = "\
+client,available,held,total,locked
+1,1.5000,0.0000,1.5000,false
+2,2.0000,0.0000,2.0000,false
+3,6.0170,0.0000,6.0170,false
+4,4.0000,0.0000,4.0000,true
+";
+        let expected_rejected = "\
+type,client,tx,amount,error
+withdrawal,2,5,3.0000,insufficient_funds
+chargeback,3,7,,not_disputed
+dispute,3,7,,transaction_closed
+";
+
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_reader(input.as_bytes());
+        let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
+        let mut rejections = csv::WriterBuilder::new().from_writer(vec![]);
+        super::process_csv_parallel(&mut reader, &mut writer, &mut rejections, 4, false).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let rejected = String::from_utf8(rejections.into_inner().unwrap()).unwrap();
+
+        assert_eq!(output, expected_output);
+        assert_eq!(rejected, expected_rejected);
+    }
+
+    #[test]
+    fn process_csv_parallel_preserves_rejection_order_across_workers() {
+        // Clients 2 and 5 land on different workers (2 % 4 != 5 % 4), so
+        // without sequence-based sorting the rejection log would come out
+        // in worker-join order instead of input order.
+        let input = "\
+type,client,tx,amount
+withdrawal,2,1,5.0
+withdrawal,5,2,5.0
+";
+        let expected_rejected = "\
+type,client,tx,amount,error
+withdrawal,2,1,5.0000,insufficient_funds
+withdrawal,5,2,5.0000,insufficient_funds
+";
+
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_reader(input.as_bytes());
+        let mut writer = csv::WriterBuilder::new().from_writer(vec![]);
+        let mut rejections = csv::WriterBuilder::new().from_writer(vec![]);
+        super::process_csv_parallel(&mut reader, &mut writer, &mut rejections, 4, false).unwrap();
+        let rejected = String::from_utf8(rejections.into_inner().unwrap()).unwrap();
+
+        assert_eq!(rejected, expected_rejected);
     }
 }
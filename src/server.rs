@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::Result;
+
+use crate::account::Account;
+use crate::error::LedgerError;
+use crate::transaction::Transaction;
+use crate::types::ClientId;
+
+type Accounts = Arc<Mutex<BTreeMap<ClientId, Account>>>;
+
+/// Runs a TCP server that ingests transactions and answers balance queries
+/// over a line-delimited protocol.
+///
+/// Each line is either a transaction in the same `type,client,tx,amount` CSV
+/// shape accepted by `process_csv_file`, or a `query,<client>` line asking
+/// for that client's current `available,held,total,locked` snapshot. Every
+/// connection shares one account map behind a single mutex, so a client's
+/// transactions are always applied in the order they are received, even
+/// across concurrent connections for the same client.
+pub fn run(addr: &str, allow_withdrawal_disputes: bool) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let accounts: Accounts = Arc::new(Mutex::new(BTreeMap::new()));
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let accounts = Arc::clone(&accounts);
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &accounts, allow_withdrawal_disputes) {
+                eprintln!("Warning: connection error: {}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    accounts: &Accounts,
+    allow_withdrawal_disputes: bool,
+) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        writeln!(
+            writer,
+            "{}",
+            handle_line(&line, accounts, allow_withdrawal_disputes)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Handles a single protocol line and returns the response to write back.
+fn handle_line(line: &str, accounts: &Accounts, allow_withdrawal_disputes: bool) -> String {
+    match line.strip_prefix("query,") {
+        Some(client) => handle_query(client.trim(), accounts),
+        None => handle_transaction_line(line, accounts, allow_withdrawal_disputes),
+    }
+}
+
+fn handle_query(client: &str, accounts: &Accounts) -> String {
+    let client_id: ClientId = match client.parse() {
+        Ok(client_id) => client_id,
+        Err(_) => return format!("error,invalid client id: {}", client),
+    };
+
+    match accounts.lock().unwrap().get(&client_id) {
+        Some(account) => format!(
+            "{},{},{},{}",
+            account.available, account.held, account.total, account.locked
+        ),
+        None => format!("error,unknown client {}", client_id),
+    }
+}
+
+fn handle_transaction_line(
+    line: &str,
+    accounts: &Accounts,
+    allow_withdrawal_disputes: bool,
+) -> String {
+    let transaction = match parse_transaction(line) {
+        Ok(transaction) => transaction,
+        Err(e) => return format!("error,{}", e),
+    };
+
+    if transaction.requires_amount() && transaction.amount.is_none() {
+        return format!("error,{}", LedgerError::MissingAmount);
+    }
+
+    let mut accounts = accounts.lock().unwrap();
+    let account = accounts.entry(transaction.client_id).or_insert_with(|| {
+        Account::new(transaction.client_id)
+            .with_withdrawal_disputes_allowed(allow_withdrawal_disputes)
+    });
+
+    match account.execute(transaction) {
+        Ok(()) => "ok".to_string(),
+        Err(error) => format!("error,{}", error),
+    }
+}
+
+/// Parses a single line in the `type,client,tx,amount` shape also accepted by
+/// `process_csv_file`. Disputes/resolves/chargebacks omit the trailing
+/// `amount` field, so headers are supplied manually and `flexible` is set to
+/// allow that short row to deserialize `amount` as `None`, the same way a
+/// ragged row in a CSV file does.
+fn parse_transaction(line: &str) -> Result<Transaction> {
+    let with_headers = format!("type,client,tx,amount\n{line}");
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_reader(with_headers.as_bytes());
+    reader
+        .deserialize::<Transaction>()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("empty transaction line"))?
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounts() -> Accounts {
+        Arc::new(Mutex::new(BTreeMap::new()))
+    }
+
+    #[test]
+    fn deposit_then_query() {
+        let accounts = accounts();
+        assert_eq!(handle_line("deposit,1,1,2.5", &accounts, false), "ok");
+        assert_eq!(
+            handle_line("query,1", &accounts, false),
+            "2.5000,0.0000,2.5000,false"
+        );
+    }
+
+    #[test]
+    fn query_unknown_client_is_an_error() {
+        let accounts = accounts();
+        assert_eq!(
+            handle_line("query,1", &accounts, false),
+            "error,unknown client 1"
+        );
+    }
+
+    #[test]
+    fn dispute_then_chargeback_over_the_wire() {
+        let accounts = accounts();
+        assert_eq!(handle_line("deposit,1,1,5.0", &accounts, false), "ok");
+        assert_eq!(handle_line("dispute,1,1", &accounts, false), "ok");
+        assert_eq!(
+            handle_line("query,1", &accounts, false),
+            "0.0000,5.0000,5.0000,false"
+        );
+        assert_eq!(handle_line("chargeback,1,1", &accounts, false), "ok");
+        assert_eq!(
+            handle_line("query,1", &accounts, false),
+            "0.0000,0.0000,0.0000,true"
+        );
+    }
+
+    #[test]
+    fn dispute_of_withdrawal_allowed_when_enabled() {
+        let accounts = accounts();
+        assert_eq!(handle_line("deposit,1,1,5.0", &accounts, true), "ok");
+        assert_eq!(handle_line("withdrawal,1,2,2.0", &accounts, true), "ok");
+        assert_eq!(handle_line("dispute,1,2", &accounts, true), "ok");
+        assert_eq!(
+            handle_line("query,1", &accounts, true),
+            "3.0000,2.0000,5.0000,false"
+        );
+        assert_eq!(handle_line("chargeback,1,2", &accounts, true), "ok");
+        assert_eq!(
+            handle_line("query,1", &accounts, true),
+            "5.0000,0.0000,5.0000,true"
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::types::{Amount, ClientId, TransactionId};
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TransactionType {
     Deposit,
@@ -12,6 +12,21 @@ pub enum TransactionType {
     Chargeback,
 }
 
+/// The lifecycle of a processed transaction with respect to disputes.
+///
+/// The only legal transitions are `Processed -> Disputed`, `Disputed ->
+/// Resolved`, and `Disputed -> ChargedBack`. In particular a `Resolved`
+/// transaction never moves back to `Processed`, so it cannot be disputed a
+/// second time.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TxState {
+    #[default]
+    Processed,
+    Disputed,
+    Resolved,
+    ChargedBack,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Transaction {
     #[serde(rename = "type")]
@@ -21,8 +36,8 @@ pub struct Transaction {
     #[serde(rename = "tx")]
     pub transaction_id: TransactionId,
     pub amount: Option<Amount>,
-    #[serde(skip_deserializing)]
-    pub under_dispute: bool,
+    #[serde(skip_deserializing, default)]
+    pub state: TxState,
 }
 
 impl Transaction {
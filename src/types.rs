@@ -0,0 +1,154 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub type ClientId = u16;
+pub type TransactionId = u32;
+
+/// Scale factor of `Amount`: 10^4, i.e. four decimal digits after the point.
+const SCALE: i64 = 10_000;
+
+/// A fixed-point decimal amount, scaled by [`SCALE`] and backed by an `i64`.
+///
+/// Representing money as an integer number of ten-thousandths avoids the
+/// rounding error that accumulates when `f64` is used for arithmetic across
+/// many deposits/withdrawals.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseAmountError {
+    Invalid,
+    TooManyFractionalDigits,
+}
+
+impl fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseAmountError::Invalid => write!(f, "invalid amount"),
+            ParseAmountError::TooManyFractionalDigits => {
+                write!(f, "amount has more than 4 fractional digits")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseAmountError {}
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    /// Adds two amounts, returning `None` on overflow instead of wrapping.
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    /// Subtracts `other` from `self`, returning `None` on overflow instead of wrapping.
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let mut parts = unsigned.splitn(2, '.');
+        let integer_part = parts.next().unwrap_or("");
+        let fractional_part = parts.next().unwrap_or("");
+
+        if fractional_part.len() > 4 {
+            return Err(ParseAmountError::TooManyFractionalDigits);
+        }
+
+        let integer: i64 = if integer_part.is_empty() {
+            0
+        } else {
+            integer_part.parse().map_err(|_| ParseAmountError::Invalid)?
+        };
+
+        let mut fractional: i64 = if fractional_part.is_empty() {
+            0
+        } else {
+            fractional_part
+                .parse()
+                .map_err(|_| ParseAmountError::Invalid)?
+        };
+        for _ in fractional_part.len()..4 {
+            fractional *= 10;
+        }
+
+        let magnitude = integer
+            .checked_mul(SCALE)
+            .and_then(|scaled| scaled.checked_add(fractional))
+            .ok_or(ParseAmountError::Invalid)?;
+        Ok(Amount(if negative { -magnitude } else { magnitude }))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let magnitude = self.0.unsigned_abs();
+        write!(f, "{sign}{}.{:04}", magnitude / SCALE as u64, magnitude % SCALE as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_exact_decimal() {
+        assert_eq!("1.017".parse::<Amount>().unwrap(), Amount(10170));
+        assert_eq!("2".parse::<Amount>().unwrap(), Amount(20000));
+        assert_eq!("-1.5".parse::<Amount>().unwrap(), Amount(-15000));
+    }
+
+    #[test]
+    fn rejects_overflowing_amount() {
+        assert_eq!(
+            "1000000000000000.0000".parse::<Amount>(),
+            Err(ParseAmountError::Invalid)
+        );
+    }
+
+    #[test]
+    fn rejects_too_many_fractional_digits() {
+        assert_eq!(
+            "1.00001".parse::<Amount>(),
+            Err(ParseAmountError::TooManyFractionalDigits)
+        );
+    }
+
+    #[test]
+    fn displays_four_fractional_digits() {
+        assert_eq!(Amount(10170).to_string(), "1.0170");
+        assert_eq!(Amount(-15000).to_string(), "-1.5000");
+    }
+}